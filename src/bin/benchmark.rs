@@ -0,0 +1,181 @@
+// Synthetic-corpus benchmark harness for tuning `WriterProperties`, mirroring
+// the scale-factor approach from the arrow/datafusion parquet benchmarks.
+// Runs the real `convert::jsonl_to_parquet` write path across a matrix of
+// configurations, rather than a hand-rolled writer, so the numbers reflect
+// what the CLI actually does.
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use arrow::datatypes::DataType;
+use clap::Parser;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::EnabledStatistics;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use parquet_hf_rs::convert::{self, FaultToleranceConfig, WriterConfig};
+use parquet_hf_rs::schema::{ColumnSpec, Extractor, SchemaConfig};
+
+/// Bytes/row is a rough FineWeb-shard average; used only to size the
+/// synthetic corpus to roughly `scale_factor` GB.
+const APPROX_BYTES_PER_ROW: f64 = 1200.0;
+
+const LANGUAGES: &[&str] = &["en", "es", "fr", "de", "ru", "zh", "ja", "pt", "it", "ar"];
+
+#[derive(Parser)]
+#[clap(author, version, about = "Benchmark jsonl->parquet WriterProperties combinations", long_about = None)]
+struct BenchArgs {
+    /// Approximate corpus size, in GB, at 1.0.
+    #[arg(long, default_value_t = 1.0)]
+    scale_factor: f64,
+
+    /// Where to cache the generated JSONL corpus. Reused across runs if present.
+    #[arg(long, default_value = "bench_corpus.jsonl")]
+    path: PathBuf,
+
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+}
+
+/// A Zipf-like rank weighting (s=1.0) over `LANGUAGES`, so low-index
+/// languages ("en") dominate the corpus the way real crawl data does.
+fn zipf_pick(rng: &mut StdRng) -> &'static str {
+    let weights: Vec<f64> = (1..=LANGUAGES.len()).map(|rank| 1.0 / rank as f64).collect();
+    let total: f64 = weights.iter().sum();
+    let mut x = rng.gen::<f64>() * total;
+    for (idx, w) in weights.iter().enumerate() {
+        if x < *w {
+            return LANGUAGES[idx];
+        }
+        x -= w;
+    }
+    LANGUAGES[LANGUAGES.len() - 1]
+}
+
+fn random_lorem(rng: &mut StdRng, min_words: usize, max_words: usize) -> String {
+    const WORDS: &[&str] = &[
+        "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit",
+        "sed", "do", "eiusmod", "tempor", "incididunt", "ut", "labore", "et", "dolore",
+        "magna", "aliqua", "crawl", "shard", "corpus", "token", "sentence", "web", "page",
+    ];
+    let n = rng.gen_range(min_words..=max_words);
+    (0..n).map(|_| WORDS[rng.gen_range(0..WORDS.len())]).collect::<Vec<_>>().join(" ")
+}
+
+fn generate_corpus(args: &BenchArgs) -> std::io::Result<usize> {
+    if args.path.exists() {
+        let existing = fs::read_to_string(&args.path)?;
+        return Ok(existing.lines().count());
+    }
+
+    let target_bytes = args.scale_factor * 1_000_000_000.0;
+    let num_rows = (target_bytes / APPROX_BYTES_PER_ROW).round() as usize;
+
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut file = fs::File::create(&args.path)?;
+
+    for i in 0..num_rows {
+        let language = zipf_pick(&mut rng);
+        let url = format!("https://example-{}.test/page-{}", rng.gen_range(0..10_000), i);
+        let text = random_lorem(&mut rng, 50, 400);
+        let score: f64 = rng.gen_range(0.0..1.0);
+        let line = serde_json::json!({
+            "url": url,
+            "text": text,
+            "language": language,
+            "score": score,
+        });
+        writeln!(file, "{}", line)?;
+    }
+
+    Ok(num_rows)
+}
+
+/// The schema for the synthetic corpus, as a real `SchemaConfig` so the
+/// benchmark drives `convert::jsonl_to_parquet` the same way the CLI does.
+/// `dictionary` is threaded through per-config, since that's one of the
+/// knobs the matrix varies.
+fn bench_schema(dictionary_on_language: bool) -> SchemaConfig {
+    SchemaConfig {
+        columns: vec![
+            ColumnSpec { name: "url".into(), data_type: DataType::Utf8, pointer: "url".into(), nullable: false, extractor: Extractor::Direct, dictionary: false },
+            ColumnSpec { name: "text".into(), data_type: DataType::Utf8, pointer: "text".into(), nullable: false, extractor: Extractor::Direct, dictionary: false },
+            ColumnSpec { name: "language".into(), data_type: DataType::Utf8, pointer: "language".into(), nullable: false, extractor: Extractor::Direct, dictionary: dictionary_on_language },
+            ColumnSpec { name: "score".into(), data_type: DataType::Float32, pointer: "score".into(), nullable: false, extractor: Extractor::Direct, dictionary: false },
+        ],
+    }
+}
+
+struct BenchConfig {
+    label: &'static str,
+    compression: Compression,
+    dictionary: bool,
+    row_group_size: usize,
+}
+
+fn config_matrix() -> Vec<BenchConfig> {
+    let mut configs = Vec::new();
+    for &(label, level) in &[("zstd:1", 1), ("zstd:3", 3), ("zstd:9", 9), ("zstd:22", 22)] {
+        configs.push(BenchConfig {
+            label,
+            compression: Compression::ZSTD(ZstdLevel::try_new(level).unwrap()),
+            dictionary: true,
+            row_group_size: 1_000_000,
+        });
+    }
+    configs.push(BenchConfig { label: "snappy", compression: Compression::SNAPPY, dictionary: true, row_group_size: 1_000_000 });
+    configs.push(BenchConfig { label: "uncompressed", compression: Compression::UNCOMPRESSED, dictionary: true, row_group_size: 1_000_000 });
+    configs.push(BenchConfig { label: "zstd:3,no-dict", compression: Compression::ZSTD(ZstdLevel::try_new(3).unwrap()), dictionary: false, row_group_size: 1_000_000 });
+    configs.push(BenchConfig { label: "zstd:3,rg=8192", compression: Compression::ZSTD(ZstdLevel::try_new(3).unwrap()), dictionary: true, row_group_size: 8192 });
+    configs
+}
+
+/// Run the real write path (`convert::jsonl_to_parquet`) for one config and
+/// report how long it took and how big the output came out.
+fn run_config(corpus_path: &PathBuf, cfg: &BenchConfig) -> (std::time::Duration, u64) {
+    let schema_config = bench_schema(cfg.dictionary);
+    let writer_config = WriterConfig {
+        row_group_size: cfg.row_group_size,
+        compression: cfg.compression,
+        statistics_enabled: EnabledStatistics::Page,
+    };
+    let fault_config = FaultToleranceConfig::default();
+    let out_path = PathBuf::from(format!("bench_out_{}.parquet", cfg.label.replace([':', ',', '='], "_")));
+
+    let start = Instant::now();
+    convert::jsonl_to_parquet(corpus_path, &out_path, &schema_config, None, None, &writer_config, &fault_config).unwrap();
+    let elapsed = start.elapsed();
+
+    let out_bytes = fs::metadata(&out_path).unwrap().len();
+    let _ = fs::remove_file(&out_path);
+    (elapsed, out_bytes)
+}
+
+fn main() {
+    let args = BenchArgs::parse();
+
+    println!("Generating/loading synthetic corpus at {:?} (scale_factor={})...", args.path, args.scale_factor);
+    let num_rows = generate_corpus(&args).unwrap();
+    let raw_bytes = fs::metadata(&args.path).unwrap().len();
+    println!("Corpus: {} rows, {:.2} MB raw JSONL\n", num_rows, raw_bytes as f64 / 1e6);
+
+    println!(
+        "{:<18} {:>10} {:>12} {:>14} {:>14} {:>10}",
+        "config", "time(s)", "MB/s", "rows/s", "file size(MB)", "ratio"
+    );
+    for cfg in config_matrix() {
+        let (elapsed, out_bytes) = run_config(&args.path, &cfg);
+        let secs = elapsed.as_secs_f64().max(1e-9);
+        println!(
+            "{:<18} {:>10.2} {:>12.1} {:>14.0} {:>14.2} {:>10.2}",
+            cfg.label,
+            secs,
+            (raw_bytes as f64 / 1e6) / secs,
+            num_rows as f64 / secs,
+            out_bytes as f64 / 1e6,
+            raw_bytes as f64 / out_bytes.max(1) as f64,
+        );
+    }
+}
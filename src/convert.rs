@@ -0,0 +1,503 @@
+use std::sync::Arc;
+use std::io::{BufRead, Read};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Error};
+use arrow::array::ArrayRef;
+use arrow::record_batch::RecordBatch;
+use indicatif::{ProgressBar, ProgressStyle};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
+use parquet::schema::types::ColumnPath;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::filter::Predicate;
+use crate::io::{read_pathbuf_to_mem, write_mem_to_pathbuf};
+use crate::plugin::Plugin;
+use crate::schema::{find_max_item, resolve_pointer, ColumnBuilder, ColumnSpec, Extractor, SchemaConfig};
+
+/// Rows sampled from the first file when inferring a schema (no `--schema` given).
+pub const SCHEMA_INFERENCE_SAMPLE_SIZE: usize = 1000;
+
+pub fn build_pbar(num_items: usize, units: &str) -> ProgressBar {
+    let mut template = String::from(units);
+    template.push_str(" {human_pos}/{human_len} [{elapsed_precise}/{duration_precise}] [{wide_bar:.cyan/blue}]");
+    let pbar = ProgressBar::new(num_items as u64)
+        .with_style(
+            ProgressStyle::with_template(&template).unwrap()
+        );
+
+    pbar.inc(0);
+    pbar
+}
+
+
+pub fn replace_extension(path: &PathBuf) -> PathBuf {
+    let path = path.clone();
+    let regex = Regex::new(r"\.jsonl?\.(?:zstd|gz)$").unwrap();
+    let path_str = path.to_str().unwrap();
+
+    let output_path = if regex.is_match(path_str) {
+        let new_path = regex.replace(path_str, ".parquet");
+        let path = PathBuf::from(new_path.into_owned());
+        path
+    } else {
+        path
+    };
+    output_path
+}
+
+
+/// Build the default schema config for a FineWeb-style corpus, matching the
+/// six columns this tool used to hardcode before the schema subsystem existed.
+pub fn default_fineweb_schema() -> SchemaConfig {
+    use arrow::datatypes::DataType;
+
+    SchemaConfig {
+        columns: vec![
+            ColumnSpec { name: "text".into(), data_type: DataType::Utf8, pointer: "text".into(), nullable: false, extractor: Extractor::Direct, dictionary: false },
+            ColumnSpec { name: "url".into(), data_type: DataType::Utf8, pointer: "url".into(), nullable: false, extractor: Extractor::Direct, dictionary: false },
+            ColumnSpec { name: "id".into(), data_type: DataType::Utf8, pointer: "metadata.WARC-Record-ID".into(), nullable: true, extractor: Extractor::Direct, dictionary: false },
+            ColumnSpec { name: "language".into(), data_type: DataType::Utf8, pointer: "language_id_whole_page_fasttext".into(), nullable: true, extractor: Extractor::MaxValueKey, dictionary: true },
+            ColumnSpec { name: "language_score".into(), data_type: DataType::Float32, pointer: "language_id_whole_page_fasttext".into(), nullable: true, extractor: Extractor::MaxValueScore, dictionary: false },
+            ColumnSpec { name: "fasttext_score".into(), data_type: DataType::Float32, pointer: "fasttext_openhermes_reddit_eli5_vs_rw_v2_bigram_200k_train_prob".into(), nullable: true, extractor: Extractor::Direct, dictionary: false },
+        ],
+    }
+}
+
+/// Resolve the schema to use for this run: load `--schema` if given,
+/// otherwise infer one from the first input file's contents, falling back
+/// to the hardcoded FineWeb schema if inference itself can't make sense of
+/// that file (e.g. it's empty, or its rows aren't JSON objects).
+pub fn resolve_schema(schema_arg: &Option<PathBuf>, first_input: &PathBuf) -> Result<SchemaConfig, Error> {
+    if let Some(path) = schema_arg {
+        return SchemaConfig::from_path(path);
+    }
+    let mut contents = String::new();
+    read_pathbuf_to_mem(first_input)?.read_to_string(&mut contents)?;
+    match crate::schema::infer_schema(&contents, SCHEMA_INFERENCE_SAMPLE_SIZE) {
+        Ok(config) if !config.columns.is_empty() => Ok(config),
+        Ok(_) => {
+            eprintln!("schema inference found no columns, falling back to the default FineWeb schema");
+            Ok(default_fineweb_schema())
+        }
+        Err(e) => {
+            eprintln!("schema inference failed ({}), falling back to the default FineWeb schema", e);
+            Ok(default_fineweb_schema())
+        }
+    }
+}
+
+/// Turn on dictionary encoding for the named columns, on top of whatever
+/// `--schema` or `infer_schema` produced. This is the quick `--dictionary-
+/// column` path for a handful of low-cardinality columns (e.g. `language`)
+/// without hand-writing a full `--schema` config just to set one flag.
+pub fn apply_dictionary_overrides(schema_config: &mut SchemaConfig, columns: &[String]) -> Result<(), Error> {
+    for name in columns {
+        let spec = schema_config
+            .columns
+            .iter_mut()
+            .find(|c| &c.name == name)
+            .ok_or_else(|| anyhow!("--dictionary-column {:?}: no such column in schema", name))?;
+        spec.dictionary = true;
+    }
+    Ok(())
+}
+
+/// Per-file row counts reported back to the progress summary.
+pub struct ConversionStats {
+    pub kept: usize,
+    pub dropped: usize,
+    /// Lines that failed to parse or were missing a required column;
+    /// written to the `<output>.errors.jsonl` sidecar rather than the row
+    /// counts above.
+    pub quarantined: usize,
+    /// 0-based index (into `input_path`'s lines) of each row that was
+    /// actually written to the output, in the order the rows were
+    /// written. `--verify` needs this to line a reconstructed Parquet row
+    /// back up with its source line, since dropped/quarantined lines make
+    /// "row N" and "line N" diverge as soon as anything is filtered out.
+    pub kept_line_indices: Vec<usize>,
+}
+
+/// How to handle malformed lines and missing required fields.
+#[derive(Debug, Clone)]
+pub struct FaultToleranceConfig {
+    /// Abort the whole file on the first bad line, as the original
+    /// unwrap-everywhere code did, instead of quarantining it.
+    pub strict: bool,
+    /// Abort the file once more than this many lines have been quarantined.
+    pub max_errors: Option<usize>,
+}
+
+impl Default for FaultToleranceConfig {
+    fn default() -> FaultToleranceConfig {
+        FaultToleranceConfig { strict: false, max_errors: None }
+    }
+}
+
+/// One quarantined line: the raw text plus why it was rejected.
+struct QuarantinedLine {
+    raw_line: String,
+    reason: String,
+}
+
+/// A single column's extracted value, kept around long enough to both
+/// validate required-ness and append to its builder, without re-running
+/// the extractor twice.
+enum ExtractedValue<'a> {
+    Value(Option<&'a Value>),
+    Str(Option<&'a str>),
+    F64(Option<f64>),
+}
+
+impl<'a> ExtractedValue<'a> {
+    fn is_present(&self) -> bool {
+        match self {
+            ExtractedValue::Value(v) => v.is_some(),
+            ExtractedValue::Str(v) => v.is_some(),
+            ExtractedValue::F64(v) => v.is_some(),
+        }
+    }
+
+    fn append_to(&self, builder: &mut ColumnBuilder) {
+        match self {
+            ExtractedValue::Value(v) => builder.append_value(*v),
+            ExtractedValue::Str(v) => builder.append_str(*v),
+            ExtractedValue::F64(v) => builder.append_f64(*v),
+        }
+    }
+}
+
+fn extract_columns<'a>(json: &'a Value, schema_config: &SchemaConfig) -> Vec<ExtractedValue<'a>> {
+    schema_config
+        .columns
+        .iter()
+        .map(|spec| match &spec.extractor {
+            Extractor::Direct => ExtractedValue::Value(resolve_pointer(json, &spec.pointer)),
+            Extractor::MaxValueKey => ExtractedValue::Str(find_max_item(json, &spec.pointer).map(|(k, _)| k)),
+            Extractor::MaxValueScore => ExtractedValue::F64(find_max_item(json, &spec.pointer).map(|(_, v)| v)),
+        })
+        .collect()
+}
+
+/// The first non-nullable column with no value, if any, so the caller can
+/// quarantine the row with a useful reason instead of writing a parquet
+/// null into a required column.
+fn missing_required_column<'a>(schema_config: &'a SchemaConfig, extracted: &[ExtractedValue]) -> Option<&'a str> {
+    schema_config
+        .columns
+        .iter()
+        .zip(extracted.iter())
+        .find(|(spec, value)| !spec.nullable && !value.is_present())
+        .map(|(spec, _)| spec.name.as_str())
+}
+
+/// Sidecar path for quarantined lines: `<output>.errors.jsonl`.
+fn errors_sidecar_path(output_path: &PathBuf) -> PathBuf {
+    let mut path_str = output_path.to_string_lossy().into_owned();
+    path_str.push_str(".errors.jsonl");
+    PathBuf::from(path_str)
+}
+
+/// Knobs for `WriterProperties`, exposed as CLI flags instead of the old
+/// fixed `ZstdLevel::default()`.
+#[derive(Debug, Clone)]
+pub struct WriterConfig {
+    pub row_group_size: usize,
+    pub compression: Compression,
+    pub statistics_enabled: EnabledStatistics,
+}
+
+impl Default for WriterConfig {
+    fn default() -> WriterConfig {
+        WriterConfig {
+            row_group_size: 1_000_000,
+            compression: Compression::ZSTD(ZstdLevel::default()),
+            statistics_enabled: EnabledStatistics::Page,
+        }
+    }
+}
+
+/// Parse a `--compression` value like `zstd:9`, `zstd`, `snappy`, or `none`.
+pub fn parse_compression(s: &str) -> Result<Compression, Error> {
+    if let Some(level_str) = s.strip_prefix("zstd:") {
+        let level: i32 = level_str
+            .parse()
+            .map_err(|_| anyhow!("--compression: invalid zstd level {:?}", level_str))?;
+        return Ok(Compression::ZSTD(ZstdLevel::try_new(level)?));
+    }
+    match s {
+        "zstd" => Ok(Compression::ZSTD(ZstdLevel::default())),
+        "snappy" => Ok(Compression::SNAPPY),
+        "none" | "uncompressed" => Ok(Compression::UNCOMPRESSED),
+        other => Err(anyhow!("--compression: unknown value {:?} (expected zstd:LEVEL, zstd, snappy, or none)", other)),
+    }
+}
+
+/// Parse a `--statistics` value: `none`, `chunk`, or `page` (the Parquet default).
+pub fn parse_statistics(s: &str) -> Result<EnabledStatistics, Error> {
+    match s {
+        "none" => Ok(EnabledStatistics::None),
+        "chunk" => Ok(EnabledStatistics::Chunk),
+        "page" => Ok(EnabledStatistics::Page),
+        other => Err(anyhow!("--statistics: unknown value {:?} (expected none, chunk, or page)", other)),
+    }
+}
+
+pub fn jsonl_to_parquet(
+    input_path: &PathBuf,
+    output_path: &PathBuf,
+    schema_config: &SchemaConfig,
+    predicate: Option<&Predicate>,
+    plugin: Option<&Plugin>,
+    writer_config: &WriterConfig,
+    fault_config: &FaultToleranceConfig,
+) -> Result<ConversionStats, Error> {
+    let contents = read_pathbuf_to_mem(input_path).unwrap();
+
+    let mut builders: Vec<ColumnBuilder> = schema_config
+        .columns
+        .iter()
+        .map(ColumnBuilder::new)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut kept = 0usize;
+    let mut dropped = 0usize;
+    let mut quarantine: Vec<QuarantinedLine> = Vec::new();
+    let mut kept_line_indices: Vec<usize> = Vec::new();
+
+    macro_rules! quarantine_or_abort {
+        ($line:expr, $reason:expr) => {{
+            if fault_config.strict {
+                return Err(anyhow!("{:?}: {}", input_path, $reason));
+            }
+            quarantine.push(QuarantinedLine { raw_line: $line.to_string(), reason: $reason });
+            if let Some(max_errors) = fault_config.max_errors {
+                if quarantine.len() > max_errors {
+                    return Err(anyhow!(
+                        "{:?}: exceeded --max-errors {} ({} lines quarantined so far)",
+                        input_path, max_errors, quarantine.len()
+                    ));
+                }
+            }
+            continue;
+        }};
+    }
+
+    for (line_idx, line) in contents.lines().enumerate() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => quarantine_or_abort!("<unreadable line>", format!("line read error: {}", e)),
+        };
+
+        // A plugin replaces both the JSON parse and the per-column
+        // extractors: it hands back column name -> value directly, or
+        // `None` to signal "skip this row".
+        if let Some(plugin) = plugin {
+            let columns = match plugin.extract(&line) {
+                Ok(Some(columns)) => columns,
+                Ok(None) => { dropped += 1; continue; }
+                Err(e) => quarantine_or_abort!(line, format!("plugin extract error: {}", e)),
+            };
+
+            // A plugin replaces JSON parsing, but --filter still runs
+            // against whatever columns it handed back, the same as the
+            // non-plugin path below.
+            if let Some(predicate) = predicate {
+                let as_json = Value::Object(columns.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+                if !predicate.eval(&as_json) {
+                    dropped += 1;
+                    continue;
+                }
+            }
+
+            if let Some(missing) = schema_config.columns.iter().find(|spec| !spec.nullable && !columns.contains_key(&spec.name)) {
+                quarantine_or_abort!(line, format!("missing required field {:?}", missing.name));
+            }
+            kept += 1;
+            kept_line_indices.push(line_idx);
+            for (spec, builder) in schema_config.columns.iter().zip(builders.iter_mut()) {
+                builder.append_value(columns.get(&spec.name));
+            }
+            continue;
+        }
+
+        let json: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(e) => quarantine_or_abort!(line, format!("JSON parse error: {}", e)),
+        };
+
+        let extracted = extract_columns(&json, schema_config);
+
+        // --filter runs against the extracted columns, keyed by column
+        // name, so it sees the same values the Parquet file will hold.
+        if let Some(predicate) = predicate {
+            let as_json = Value::Object(
+                schema_config.columns.iter().zip(extracted.iter())
+                    .filter_map(|(spec, value)| {
+                        let v = match value {
+                            ExtractedValue::Value(v) => v.cloned(),
+                            ExtractedValue::Str(s) => s.map(|s| Value::String(s.to_string())),
+                            ExtractedValue::F64(f) => f.map(Value::from),
+                        }?;
+                        Some((spec.name.clone(), v))
+                    })
+                    .collect(),
+            );
+            if !predicate.eval(&as_json) {
+                dropped += 1;
+                continue;
+            }
+        }
+
+        if let Some(missing) = missing_required_column(schema_config, &extracted) {
+            quarantine_or_abort!(line, format!("missing required field {:?}", missing));
+        }
+
+        kept += 1;
+        kept_line_indices.push(line_idx);
+        for (value, builder) in extracted.iter().zip(builders.iter_mut()) {
+            value.append_to(builder);
+        }
+    }
+
+    if !quarantine.is_empty() {
+        let sidecar_path = errors_sidecar_path(output_path);
+        let mut sidecar = String::new();
+        for q in &quarantine {
+            let entry = serde_json::json!({ "line": q.raw_line, "error": q.reason });
+            sidecar.push_str(&serde_json::to_string(&entry)?);
+            sidecar.push('\n');
+        }
+        write_mem_to_pathbuf(sidecar.as_bytes(), &sidecar_path)?;
+    }
+
+    let arrays: Vec<ArrayRef> = builders.iter_mut().map(|b| b.finish()).collect();
+
+    let schema = schema_config.arrow_schema();
+    let batch = RecordBatch::try_new(Arc::new(schema.clone()), arrays)?;
+
+    let mut buf = Vec::new();
+    {
+        let mut props_builder = WriterProperties::builder()
+            .set_compression(writer_config.compression)
+            .set_max_row_group_size(writer_config.row_group_size)
+            .set_statistics_enabled(writer_config.statistics_enabled);
+
+        // Per-column dictionary enable/disable, driven by each ColumnSpec
+        // rather than the writer-wide default.
+        for spec in &schema_config.columns {
+            let path = ColumnPath::from(spec.name.as_str());
+            props_builder = props_builder.set_column_dictionary_enabled(path, spec.dictionary);
+        }
+        let props = props_builder.build();
+
+        let mut writer = ArrowWriter::try_new(&mut buf, Arc::new(schema), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+    }
+
+    write_mem_to_pathbuf(&buf, output_path).unwrap();
+    Ok(ConversionStats { kept, dropped, quarantined: quarantine.len(), kept_line_indices })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::DataType;
+    use std::io::Write;
+
+    fn single_column_schema() -> SchemaConfig {
+        SchemaConfig {
+            columns: vec![ColumnSpec {
+                name: "text".into(),
+                data_type: DataType::Utf8,
+                pointer: "text".into(),
+                nullable: false,
+                extractor: Extractor::Direct,
+                dictionary: false,
+            }],
+        }
+    }
+
+    fn write_temp_jsonl(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn quarantines_malformed_lines_by_default() {
+        let input = write_temp_jsonl(
+            "convert_test_quarantine.jsonl",
+            "{\"text\": \"ok\"}\nnot json\n{\"text\": \"also ok\"}\n",
+        );
+        let output = std::env::temp_dir().join("convert_test_quarantine.parquet");
+
+        let stats = jsonl_to_parquet(
+            &input, &output, &single_column_schema(), None, None,
+            &WriterConfig::default(), &FaultToleranceConfig::default(),
+        ).unwrap();
+
+        assert_eq!(stats.kept, 2);
+        assert_eq!(stats.quarantined, 1);
+        assert_eq!(stats.kept_line_indices, vec![0, 2]);
+        assert!(errors_sidecar_path(&output).exists());
+
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+        let _ = std::fs::remove_file(errors_sidecar_path(&output));
+    }
+
+    #[test]
+    fn strict_aborts_on_first_bad_line() {
+        let input = write_temp_jsonl("convert_test_strict.jsonl", "{\"text\": \"ok\"}\nnot json\n");
+        let output = std::env::temp_dir().join("convert_test_strict.parquet");
+        let fault_config = FaultToleranceConfig { strict: true, max_errors: None };
+
+        let result = jsonl_to_parquet(
+            &input, &output, &single_column_schema(), None, None,
+            &WriterConfig::default(), &fault_config,
+        );
+        assert!(result.is_err());
+
+        let _ = std::fs::remove_file(&input);
+    }
+
+    #[test]
+    fn dictionary_overrides_flip_named_columns_only() {
+        let mut schema_config = single_column_schema();
+        let mut other = schema_config.columns[0].clone();
+        other.name = "url".into();
+        schema_config.columns.push(other);
+        apply_dictionary_overrides(&mut schema_config, &["text".to_string()]).unwrap();
+        assert!(schema_config.columns[0].dictionary);
+        assert!(!schema_config.columns[1].dictionary);
+    }
+
+    #[test]
+    fn dictionary_overrides_reject_unknown_column() {
+        let mut schema_config = single_column_schema();
+        let result = apply_dictionary_overrides(&mut schema_config, &["no_such_column".to_string()]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn max_errors_aborts_once_threshold_exceeded() {
+        let input = write_temp_jsonl("convert_test_max_errors.jsonl", "bad1\nbad2\nbad3\n");
+        let output = std::env::temp_dir().join("convert_test_max_errors.parquet");
+        let fault_config = FaultToleranceConfig { strict: false, max_errors: Some(1) };
+
+        let result = jsonl_to_parquet(
+            &input, &output, &single_column_schema(), None, None,
+            &WriterConfig::default(), &fault_config,
+        );
+        assert!(result.is_err());
+
+        let _ = std::fs::remove_file(&input);
+    }
+}
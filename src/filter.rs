@@ -0,0 +1,228 @@
+use anyhow::{anyhow, Error};
+use serde_json::Value;
+
+use crate::schema::resolve_pointer;
+
+/*=================================================================
+=                       FILTER EXPRESSIONS                        =
+=================================================================*/
+
+/// A row predicate compiled once from a `--filter` expression string and
+/// evaluated against each row's extracted columns (a JSON object keyed by
+/// column name) before it's appended to the column builders.
+#[derive(Debug, Clone)]
+pub enum Predicate {
+    Compare { pointer: String, op: CompareOp, rhs: Rhs },
+    IsNull { pointer: String },
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+pub enum Rhs {
+    Number(f64),
+    Str(String),
+}
+
+impl Predicate {
+    pub fn eval(&self, json: &Value) -> bool {
+        match self {
+            Predicate::Compare { pointer, op, rhs } => {
+                let Some(value) = resolve_pointer(json, pointer) else {
+                    return false;
+                };
+                compare(value, *op, rhs)
+            }
+            Predicate::IsNull { pointer } => resolve_pointer(json, pointer).is_none(),
+            Predicate::And(a, b) => a.eval(json) && b.eval(json),
+            Predicate::Or(a, b) => a.eval(json) || b.eval(json),
+        }
+    }
+}
+
+fn compare(value: &Value, op: CompareOp, rhs: &Rhs) -> bool {
+    match rhs {
+        Rhs::Number(rhs_num) => {
+            let Some(lhs_num) = value.as_f64() else {
+                return false;
+            };
+            match op {
+                CompareOp::Eq => lhs_num == *rhs_num,
+                CompareOp::Ne => lhs_num != *rhs_num,
+                CompareOp::Lt => lhs_num < *rhs_num,
+                CompareOp::Le => lhs_num <= *rhs_num,
+                CompareOp::Gt => lhs_num > *rhs_num,
+                CompareOp::Ge => lhs_num >= *rhs_num,
+            }
+        }
+        Rhs::Str(rhs_str) => {
+            let Some(lhs_str) = value.as_str() else {
+                return false;
+            };
+            match op {
+                CompareOp::Eq => lhs_str == rhs_str,
+                CompareOp::Ne => lhs_str != rhs_str,
+                CompareOp::Lt => lhs_str < rhs_str.as_str(),
+                CompareOp::Le => lhs_str <= rhs_str.as_str(),
+                CompareOp::Gt => lhs_str > rhs_str.as_str(),
+                CompareOp::Ge => lhs_str >= rhs_str.as_str(),
+            }
+        }
+    }
+}
+
+/*=================================================================
+=                             PARSING                             =
+=================================================================*/
+
+/// Compile a small expression grammar into a `Predicate`:
+///
+///   expr       := term (("AND" | "OR") term)*
+///   term       := pointer op rhs | pointer "is_null"
+///   op         := "==" | "!=" | "<=" | ">=" | "<" | ">"
+///   rhs        := number | '"' string '"'
+///
+/// e.g. `language == "en" AND language_score >= 0.8`
+pub fn compile(expr: &str) -> Result<Predicate, Error> {
+    let or_parts: Vec<&str> = split_on_keyword(expr, "OR");
+    if or_parts.len() > 1 {
+        let mut iter = or_parts.into_iter();
+        let mut acc = compile(iter.next().unwrap())?;
+        for part in iter {
+            acc = Predicate::Or(Box::new(acc), Box::new(compile(part)?));
+        }
+        return Ok(acc);
+    }
+
+    let and_parts: Vec<&str> = split_on_keyword(expr, "AND");
+    if and_parts.len() > 1 {
+        let mut iter = and_parts.into_iter();
+        let mut acc = compile(iter.next().unwrap())?;
+        for part in iter {
+            acc = Predicate::And(Box::new(acc), Box::new(compile(part)?));
+        }
+        return Ok(acc);
+    }
+
+    compile_term(expr.trim())
+}
+
+/// Split `expr` on ` AND `/` OR ` the same way `str::split` would, except
+/// spans inside `"..."` string literals are never treated as split points -
+/// so `text == "foo AND bar"` stays one term instead of being torn apart
+/// mid-string.
+fn split_on_keyword<'a>(expr: &'a str, keyword: &str) -> Vec<&'a str> {
+    let needle = format!(" {} ", keyword);
+    let mut parts = Vec::new();
+    let mut start = 0usize;
+    let mut in_quotes = false;
+    let mut i = 0usize;
+    while i < expr.len() {
+        let c = expr[i..].chars().next().unwrap();
+        if c == '"' {
+            in_quotes = !in_quotes;
+            i += c.len_utf8();
+            continue;
+        }
+        if !in_quotes && expr[i..].starts_with(needle.as_str()) {
+            parts.push(&expr[start..i]);
+            i += needle.len();
+            start = i;
+            continue;
+        }
+        i += c.len_utf8();
+    }
+    parts.push(&expr[start..]);
+    parts
+}
+
+fn compile_term(term: &str) -> Result<Predicate, Error> {
+    let term = term.trim();
+
+    if let Some(pointer) = term.strip_suffix("is_null").map(|p| p.trim()) {
+        if !pointer.is_empty() {
+            return Ok(Predicate::IsNull { pointer: pointer.to_string() });
+        }
+    }
+
+    const OPS: &[(&str, CompareOp)] = &[
+        ("==", CompareOp::Eq),
+        ("!=", CompareOp::Ne),
+        ("<=", CompareOp::Le),
+        (">=", CompareOp::Ge),
+        ("<", CompareOp::Lt),
+        (">", CompareOp::Gt),
+    ];
+
+    for (token, op) in OPS {
+        if let Some(idx) = term.find(token) {
+            let pointer = term[..idx].trim().to_string();
+            let rhs_str = term[idx + token.len()..].trim();
+            let rhs = parse_rhs(rhs_str)?;
+            return Ok(Predicate::Compare { pointer, op: *op, rhs });
+        }
+    }
+
+    Err(anyhow!("filter: could not parse expression term {:?}", term))
+}
+
+fn parse_rhs(rhs: &str) -> Result<Rhs, Error> {
+    if rhs.len() >= 2 && rhs.starts_with('"') && rhs.ends_with('"') {
+        return Ok(Rhs::Str(rhs[1..rhs.len() - 1].to_string()));
+    }
+    rhs.parse::<f64>()
+        .map(Rhs::Number)
+        .map_err(|_| anyhow!("filter: could not parse right-hand side {:?}", rhs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn quoted_and_or_survive_splitting() {
+        let pred = compile(r#"text == "foo AND bar" AND language == "en""#).unwrap();
+        assert!(pred.eval(&json!({"text": "foo AND bar", "language": "en"})));
+        assert!(!pred.eval(&json!({"text": "foo AND bar", "language": "fr"})));
+        assert!(!pred.eval(&json!({"text": "something else", "language": "en"})));
+    }
+
+    #[test]
+    fn and_binds_within_each_or_branch() {
+        // `a AND b OR c AND d` is split on " OR " first, so each branch is
+        // its own `AND` term, not a flat left-to-right chain.
+        let pred = compile("score >= 0.9 AND language == \"en\" OR language == \"fr\"").unwrap();
+        assert!(pred.eval(&json!({"score": 0.95, "language": "en"})));
+        assert!(pred.eval(&json!({"score": 0.1, "language": "fr"})));
+        assert!(!pred.eval(&json!({"score": 0.1, "language": "en"})));
+    }
+
+    #[test]
+    fn is_null_checks_pointer_presence() {
+        let pred = compile("metadata.id is_null").unwrap();
+        assert!(pred.eval(&json!({"text": "x"})));
+        assert!(!pred.eval(&json!({"metadata": {"id": "abc"}})));
+    }
+
+    #[test]
+    fn numeric_and_string_comparisons() {
+        let pred = compile("score >= 0.5").unwrap();
+        assert!(pred.eval(&json!({"score": 0.5})));
+        assert!(!pred.eval(&json!({"score": 0.49})));
+
+        let pred = compile(r#"language != "en""#).unwrap();
+        assert!(pred.eval(&json!({"language": "fr"})));
+        assert!(!pred.eval(&json!({"language": "en"})));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod s3;
+pub mod io;
+pub mod schema;
+pub mod filter;
+pub mod plugin;
+pub mod convert;
+pub mod reverse;
@@ -1,34 +1,17 @@
-use std::sync::Arc;
 use std::time::Instant;
-use std::io::BufRead;
-use serde_json;
-use serde_json::Value;
-use anyhow::Error;
 use clap::Parser;
 use std::path::PathBuf;
-use crate::io::{expand_dirs, read_pathbuf_to_mem, write_mem_to_pathbuf, get_output_filename};
 use rayon::prelude::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use arrow::array::{ArrayRef, StringBuilder, Float32Builder};
-use arrow::datatypes::{DataType, Field, Schema};
-use arrow::record_batch::RecordBatch;
-use parquet::arrow::ArrowWriter;
-use parquet::file::properties::WriterProperties;
-use parquet::basic::{Compression, ZstdLevel};
-use regex::Regex;
-
-pub mod s3;
-pub mod io;
-
-
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use parquet_hf_rs::io::{expand_dirs, get_output_filename};
+use parquet_hf_rs::plugin::Plugin;
+use parquet_hf_rs::{convert, filter, reverse};
 
 /*=================================================================
 =                                  ARGS                           =
 =================================================================*/
 
-
-
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct ArgParser {
@@ -38,159 +21,160 @@ struct ArgParser {
     #[arg(long, required=true)]
     output: PathBuf,
 
+    /// Path to a JSON schema-mapping config (see `schema::SchemaConfig`).
+    /// When omitted, the schema is inferred from the first file's contents.
+    #[arg(long)]
+    schema: Option<PathBuf>,
+
+    /// Dictionary-encode this column (low-cardinality strings like
+    /// `language` benefit most). Can be passed multiple times; overrides
+    /// whichever `dictionary` value `--schema`/inference produced, without
+    /// needing a full schema config just to flip one flag.
+    #[arg(long)]
+    dictionary_column: Vec<String>,
+
+    /// Row-filtering predicate over extracted columns, e.g.
+    /// `language == "en" AND language_score >= 0.8`. Rows that fail the
+    /// predicate are dropped before being written out. See `filter::compile`.
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Path to a `wasm32-wasi` extraction plugin (see `plugin::Plugin`).
+    /// When set, the plugin's `extract` export replaces the schema's
+    /// per-column extractors entirely for each line.
+    #[arg(long)]
+    plugin: Option<PathBuf>,
+
+    /// Reverse mode: treat `--input` as Parquet files and write them back
+    /// out as `.jsonl.zstd`, one JSON object per row.
+    #[arg(long, default_value_t = false)]
+    reverse: bool,
+
+    /// After writing each Parquet file, round-trip it back to JSON and
+    /// assert the extracted columns match the source JSONL (see
+    /// `reverse::verify_round_trip`). Ignored in `--reverse` mode.
+    #[arg(long, default_value_t = false)]
+    verify: bool,
+
+    /// Target rows per Parquet row group.
+    #[arg(long, default_value_t = 1_000_000)]
+    row_group_size: usize,
+
+    /// `zstd:LEVEL`, `zstd` (default level), `snappy`, or `none`.
+    #[arg(long, default_value = "zstd")]
+    compression: String,
+
+    /// `none`, `chunk`, or `page` (the Parquet default).
+    #[arg(long, default_value = "page")]
+    statistics: String,
+
+    /// Abort a file once more than this many lines have been quarantined.
+    #[arg(long)]
+    max_errors: Option<usize>,
+
+    /// Abort a file on the first malformed or incomplete line, instead of
+    /// quarantining it to `<output>.errors.jsonl` and continuing.
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
 }
 
 
 /*=================================================================
-=                             UTILITIES.                          =
+=                                  MAIN                           =
 =================================================================*/
 
-fn build_pbar(num_items: usize, units: &str) -> ProgressBar {
-    let mut template = String::from(units);
-    template.push_str(" {human_pos}/{human_len} [{elapsed_precise}/{duration_precise}] [{wide_bar:.cyan/blue}]");
-    let pbar = ProgressBar::new(num_items as u64)
-        .with_style(
-            ProgressStyle::with_template(&template).unwrap()
-        );
-
-    pbar.inc(0);
-    pbar
-}
+fn main() {
+    let start_main = Instant::now();
+    let args = ArgParser::parse();
 
+    if args.reverse {
+        run_reverse(&args, start_main);
+        return;
+    }
 
-fn replace_extension(path: &PathBuf) -> PathBuf {
-    let path = path.clone();
-    let regex = Regex::new(r"\.jsonl?\.(?:zstd|gz)$").unwrap();
-    let path_str = path.to_str().unwrap();
-    
-    let output_path = if regex.is_match(path_str) {
-        let new_path = regex.replace(path_str, ".parquet");
-        let path = PathBuf::from(new_path.into_owned());
-        path 
-    } else {
-        path
+    let paths = expand_dirs(args.input.clone(), None).unwrap();
+    let pbar = convert::build_pbar(paths.len(), "Paths");
+
+    let mut schema_config = convert::resolve_schema(&args.schema, &paths[0]).unwrap();
+    convert::apply_dictionary_overrides(&mut schema_config, &args.dictionary_column).unwrap();
+    let predicate = args.filter.as_deref().map(filter::compile).transpose().unwrap();
+    let plugin = args.plugin.as_deref().map(Plugin::load).transpose().unwrap();
+    let writer_config = convert::WriterConfig {
+        row_group_size: args.row_group_size,
+        compression: convert::parse_compression(&args.compression).unwrap(),
+        statistics_enabled: convert::parse_statistics(&args.statistics).unwrap(),
+    };
+    let fault_config = convert::FaultToleranceConfig {
+        strict: args.strict,
+        max_errors: args.max_errors,
     };
-    output_path
-}
 
+    let total_kept = AtomicUsize::new(0);
+    let total_dropped = AtomicUsize::new(0);
+    let total_quarantined = AtomicUsize::new(0);
 
-fn _build_schema() -> arrow::datatypes::Schema {
-    let schema: arrow::datatypes::Schema = Schema::new(vec![
-        Field::new("text", DataType::Utf8, false),
-        Field::new("url", DataType::Utf8, false),
-        Field::new("id", DataType::Utf8, false),
-        Field::new("language", DataType::Utf8, false),
-        Field::new("language_score", DataType::Float32, false),
-        Field::new("fasttext_score", DataType::Float32, false)]);
-    schema 
-}
+    paths.par_iter()
+        .for_each(|p| {
+            let output_path = get_output_filename(&args.input, p, &args.output);
+            let output_path = convert::replace_extension(&output_path);
+            let stats = convert::jsonl_to_parquet(p, &output_path, &schema_config, predicate.as_ref(), plugin.as_ref(), &writer_config, &fault_config).unwrap();
+            total_kept.fetch_add(stats.kept, Ordering::Relaxed);
+            total_dropped.fetch_add(stats.dropped, Ordering::Relaxed);
+            total_quarantined.fetch_add(stats.quarantined, Ordering::Relaxed);
+            if stats.quarantined > 0 {
+                println!("[quarantine] {:?}: {} bad lines (see sidecar)", p, stats.quarantined);
+            }
+
+            if args.verify {
+                let report = reverse::verify_round_trip(p, &output_path, &schema_config, &stats.kept_line_indices, plugin.as_ref()).unwrap();
+                if report.mismatches.is_empty() {
+                    println!("[verify] {:?}: {} rows match", p, report.rows_checked);
+                } else {
+                    panic!(
+                        "[verify] {:?}: {}/{} rows had mismatches, e.g. {}",
+                        p, report.mismatches.len(), report.rows_checked, report.mismatches[0]
+                    );
+                }
+            }
 
-fn _find_max_item(json: Option<&Value>) -> Option<(&str, f64)> {
-    if json.is_none() {
-        return None;
-    }
-    let json = json.unwrap();
-    json.as_object()?
-        .iter()
-        .filter_map(|(key, value)| {
-            value.as_f64().map(|v| (key.as_str(), v))
-        })
-        .max_by(|&(_, a), &(_, b)| a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal))
-}
+            pbar.inc(1);
+        });
 
-fn jsonl_to_parquet(input_path: &PathBuf, output_path: &PathBuf) -> Result<(), Error> {
-
-    let contents = read_pathbuf_to_mem(input_path).unwrap();
-
-    let mut text_builder = StringBuilder::new();
-    let mut url_builder = StringBuilder::new();
-    let mut id_builder = StringBuilder::new();
-    let mut language_builder = StringBuilder::new();
-    let mut language_score_builder = Float32Builder::new();
-    let mut fasttext_score_builder = Float32Builder::new();
-
-    for line in contents.lines() {
-        let line = line.unwrap();
-        let json : Value = serde_json::from_str(&line).unwrap();
-
-        // MUST HAVE: text + url
-        text_builder.append_value(json["text"].as_str().unwrap());
-        url_builder.append_value(json["url"].as_str().unwrap());
-
-        // Would like-to-have: id, language, language_score, fasttext_score
-        id_builder.append_option(
-            json.get("metadata")
-                .and_then(|m| m.get("WARC-Record-ID"))
-                .and_then(|v| v.as_str())
-        );       
-        let max_language_score = _find_max_item(json.get("language_id_whole_page_fasttext"));
-        if max_language_score.is_none() {
-            language_builder.append_option(None::<String>);
-            language_score_builder.append_option(None);
-        } else {
-            let (lang_id, lang_score) = max_language_score.unwrap();
-            language_builder.append_value(lang_id);
-            language_score_builder.append_value(lang_score as f32);
-        }
-
-        let ft_score = json.get("fasttext_openhermes_reddit_eli5_vs_rw_v2_bigram_200k_train_prob");
-        if ft_score.is_none() {
-            fasttext_score_builder.append_option(None);
-        } else {
-            fasttext_score_builder.append_value(ft_score.unwrap().as_f64().unwrap() as f32);
-        }
-    }
 
-    let text_array : ArrayRef = Arc::new(text_builder.finish());
-    let url_array : ArrayRef = Arc::new(url_builder.finish());
-    let id_array : ArrayRef = Arc::new(id_builder.finish());
-    let language_array : ArrayRef = Arc::new(language_builder.finish());
-    let language_score_array : ArrayRef = Arc::new(language_score_builder.finish());
-    let fasttext_score_array : ArrayRef = Arc::new(fasttext_score_builder.finish());
-
-
-    let schema = _build_schema();
-    let batch = RecordBatch::try_new(
-        Arc::new(schema.clone()),
-        vec![text_array, url_array, id_array, language_array, language_score_array, fasttext_score_array],
-    )?;    
-
-    let mut buf = Vec::new();
-    {
-        let props = WriterProperties::builder()
-            .set_compression(Compression::ZSTD(ZstdLevel::default()))  // Use zstd compression
-            .build();        
-        let mut writer = ArrowWriter::try_new(&mut buf, Arc::new(schema), Some(props))?;
-        writer.write(&batch)?;
-        writer.close()?;
-    }    
-
-    write_mem_to_pathbuf(&buf, output_path).unwrap();
-    Ok(())
+    println!("-------------------------");
+    if predicate.is_some() {
+        println!(
+            "Kept {} rows, dropped {} rows ({:.2}% kept)",
+            total_kept.load(Ordering::Relaxed),
+            total_dropped.load(Ordering::Relaxed),
+            100.0 * total_kept.load(Ordering::Relaxed) as f64
+                / (total_kept.load(Ordering::Relaxed) + total_dropped.load(Ordering::Relaxed)).max(1) as f64
+        );
+    }
+    println!(
+        "Good rows: {}, quarantined rows: {}",
+        total_kept.load(Ordering::Relaxed),
+        total_quarantined.load(Ordering::Relaxed)
+    );
+    println!("Finishing parquet creation in {:?} seconds", start_main.elapsed().as_secs());
 }
 
-
-
-/*=================================================================
-=                                  MAIN                           =
-=================================================================*/
-
-fn main() {
-    let start_main = Instant::now();
-    let args = ArgParser::parse();
-
+fn run_reverse(args: &ArgParser, start_main: Instant) {
     let paths = expand_dirs(args.input.clone(), None).unwrap();
-    let pbar = build_pbar(paths.len(), "Paths");
+    let pbar = convert::build_pbar(paths.len(), "Paths");
+    let total_rows = AtomicUsize::new(0);
 
     paths.par_iter()
         .for_each(|p| {
             let output_path = get_output_filename(&args.input, p, &args.output);
-            let output_path = replace_extension(&output_path);
-            jsonl_to_parquet(p, &output_path).unwrap();
+            let output_path = reverse::restore_jsonl_extension(&output_path);
+            let rows = reverse::parquet_to_jsonl(p, &output_path).unwrap();
+            total_rows.fetch_add(rows, Ordering::Relaxed);
             pbar.inc(1);
         });
 
-
     println!("-------------------------");
-    println!("Finishing parquet creation in {:?} seconds", start_main.elapsed().as_secs());    
-}
\ No newline at end of file
+    println!("Wrote {} rows back to JSONL", total_rows.load(Ordering::Relaxed));
+    println!("Finishing reverse conversion in {:?} seconds", start_main.elapsed().as_secs());
+}
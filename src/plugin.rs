@@ -0,0 +1,154 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Error};
+use serde_json::Value;
+use wasmtime::{Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
+use wasmtime_wasi::sync::WasiCtxBuilder;
+use wasmtime_wasi::WasiCtx;
+
+/*=================================================================
+=                          PLUGIN HOST ABI                        =
+=================================================================*/
+//
+// A plugin is a `wasm32-wasi` module that replaces the hardcoded
+// `fasttext_*`/`_find_max_item` extraction logic with guest code, so new
+// corpora need a plugin instead of a recompile of this crate.
+//
+// Guest contract:
+//   alloc(len: i32) -> i32            allocate `len` bytes, return a pointer
+//   dealloc(ptr: i32, len: i32)        free a previously-allocated buffer
+//   extract(ptr: i32, len: i32) -> i64 read the UTF-8 JSON line at
+//                                      (ptr, len), return a packed
+//                                      (out_ptr << 32 | out_len). An
+//                                      `out_len` of 0 means "skip this row".
+//                                      Otherwise the `out_len` bytes at
+//                                      `out_ptr` are a JSON object mapping
+//                                      column name -> typed value.
+//   memory                            exported linear memory the host reads
+//                                      and writes through.
+
+/// Host-side handle to a loaded plugin module. Cheap to clone: `Engine` and
+/// `Module` are both internally `Arc`-backed. Each worker thread lazily
+/// instantiates its own `Store`/`Instance` the first time it calls
+/// `extract`, since `wasmtime::Store` isn't `Send` and rayon reuses a fixed
+/// thread pool across tasks.
+#[derive(Clone)]
+pub struct Plugin {
+    engine: Engine,
+    module: Module,
+}
+
+struct PluginInstance {
+    store: Store<WasiCtx>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: TypedFunc<(i32, i32), ()>,
+    extract: TypedFunc<(i32, i32), i64>,
+}
+
+thread_local! {
+    static INSTANCE_CACHE: RefCell<HashMap<usize, PluginInstance>> = RefCell::new(HashMap::new());
+}
+
+impl Plugin {
+    pub fn load(path: &std::path::Path) -> Result<Plugin, Error> {
+        let engine = Engine::default();
+        let module = Module::from_file(&engine, path)
+            .map_err(|e| anyhow!("plugin: failed to load wasm module {:?}: {}", path, e))?;
+        Ok(Plugin { engine, module })
+    }
+
+    /// The key of this plugin's thread-local instance slot: the address of
+    /// this `Plugin` value's own `module` field. It stays the same for as
+    /// long as the value is not moved (e.g. one `&Plugin` shared by every
+    /// worker); separate clones have separate addresses and so get
+    /// separate instances.
+    fn cache_key(&self) -> usize {
+        &self.module as *const Module as usize
+    }
+
+    fn with_instance<T>(&self, f: impl FnOnce(&mut PluginInstance) -> Result<T, Error>) -> Result<T, Error> {
+        INSTANCE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let key = self.cache_key();
+            if !cache.contains_key(&key) {
+                cache.insert(key, self.instantiate()?);
+            }
+            f(cache.get_mut(&key).unwrap())
+        })
+    }
+
+    fn instantiate(&self) -> Result<PluginInstance, Error> {
+        let wasi = WasiCtxBuilder::new().build();
+        let mut store = Store::new(&self.engine, wasi);
+        let mut linker: Linker<WasiCtx> = Linker::new(&self.engine);
+        wasmtime_wasi::sync::add_to_linker(&mut linker, |ctx| ctx)?;
+
+        let instance: Instance = linker.instantiate(&mut store, &self.module)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| anyhow!("plugin: module does not export \"memory\""))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let dealloc = instance.get_typed_func::<(i32, i32), ()>(&mut store, "dealloc")?;
+        let extract = instance.get_typed_func::<(i32, i32), i64>(&mut store, "extract")?;
+
+        Ok(PluginInstance { store, memory, alloc, dealloc, extract })
+    }
+
+    /// Run a single JSONL line through the guest `extract` function.
+    /// `Ok(None)` means the guest asked to skip this row.
+    pub fn extract(&self, line: &str) -> Result<Option<HashMap<String, Value>>, Error> {
+        self.with_instance(|inst| {
+            let in_ptr = inst.alloc.call(&mut inst.store, line.len() as i32)?;
+            inst.memory.write(&mut inst.store, in_ptr as usize, line.as_bytes())?;
+
+            let packed = inst.extract.call(&mut inst.store, (in_ptr, line.len() as i32))?;
+            inst.dealloc.call(&mut inst.store, (in_ptr, line.len() as i32))?;
+
+            let (out_ptr, out_len) = unpack_extract_result(packed);
+            if out_len == 0 {
+                return Ok(None);
+            }
+
+            let mut out_bytes = vec![0u8; out_len as usize];
+            inst.memory.read(&inst.store, out_ptr as usize, &mut out_bytes)?;
+            inst.dealloc.call(&mut inst.store, (out_ptr, out_len))?;
+
+            let value: Value = serde_json::from_slice(&out_bytes)
+                .map_err(|e| anyhow!("plugin: guest returned invalid JSON: {}", e))?;
+            let map = value
+                .as_object()
+                .ok_or_else(|| anyhow!("plugin: guest output must be a JSON object"))?
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            Ok(Some(map))
+        })
+    }
+}
+
+/// Split `extract`'s packed `(out_ptr << 32 | out_len)` return value into
+/// its `(out_ptr, out_len)` components.
+fn unpack_extract_result(packed: i64) -> (i32, i32) {
+    let out_ptr = (packed >> 32) as i32;
+    let out_len = (packed & 0xffff_ffff) as i32;
+    (out_ptr, out_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpacks_ptr_and_len_from_packed_i64() {
+        assert_eq!(unpack_extract_result((42i64 << 32) | 7), (42, 7));
+        assert_eq!(unpack_extract_result(0), (0, 0));
+    }
+
+    #[test]
+    fn zero_len_signals_skip_regardless_of_ptr() {
+        let (_, out_len) = unpack_extract_result(123i64 << 32);
+        assert_eq!(out_len, 0);
+    }
+}
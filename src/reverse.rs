@@ -0,0 +1,250 @@
+use std::io::{BufRead, Cursor};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Error};
+use arrow::array::{Array, BooleanArray, DictionaryArray, Float32Array, Float64Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Int32Type};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::io::{read_pathbuf_to_mem, write_mem_to_pathbuf};
+
+/// The inverse of `convert::replace_extension`: map a `.parquet` output path
+/// back to the `.jsonl.zstd` layout the rest of the crate reads/writes.
+pub fn restore_jsonl_extension(path: &PathBuf) -> PathBuf {
+    let path_str = path.to_str().unwrap();
+    let regex = Regex::new(r"\.parquet$").unwrap();
+    if regex.is_match(path_str) {
+        PathBuf::from(regex.replace(path_str, ".jsonl.zstd").into_owned())
+    } else {
+        path.clone()
+    }
+}
+
+/// Read every row of `path`'s Parquet file back into JSON objects, one per
+/// row, keyed by the Arrow schema's field names.
+pub fn reconstruct_rows(path: &PathBuf) -> Result<Vec<Map<String, Value>>, Error> {
+    let file = std::fs::File::open(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+
+    let mut rows: Vec<Map<String, Value>> = Vec::new();
+    for batch_result in reader {
+        let batch = batch_result?;
+        let schema = batch.schema();
+        for row in 0..batch.num_rows() {
+            let mut obj = Map::new();
+            for (col_idx, field) in schema.fields().iter().enumerate() {
+                let array = batch.column(col_idx);
+                let value = array_value_at(array.as_ref(), field.data_type(), row)?;
+                obj.insert(field.name().clone(), value);
+            }
+            rows.push(obj);
+        }
+    }
+    Ok(rows)
+}
+
+fn array_value_at(array: &dyn Array, data_type: &DataType, row: usize) -> Result<Value, Error> {
+    if array.is_null(row) {
+        return Ok(Value::Null);
+    }
+    let value = match data_type {
+        DataType::Utf8 => Value::String(
+            array.as_any().downcast_ref::<StringArray>().unwrap().value(row).to_string(),
+        ),
+        DataType::Boolean => Value::Bool(
+            array.as_any().downcast_ref::<BooleanArray>().unwrap().value(row),
+        ),
+        DataType::Int64 => Value::from(
+            array.as_any().downcast_ref::<Int64Array>().unwrap().value(row),
+        ),
+        DataType::Float32 => Value::from(
+            array.as_any().downcast_ref::<Float32Array>().unwrap().value(row) as f64,
+        ),
+        DataType::Float64 => Value::from(
+            array.as_any().downcast_ref::<Float64Array>().unwrap().value(row),
+        ),
+        DataType::Dictionary(key_type, value_type) if **key_type == DataType::Int32 && **value_type == DataType::Utf8 => {
+            let dict = array.as_any().downcast_ref::<DictionaryArray<Int32Type>>().unwrap();
+            let values = dict.values().as_any().downcast_ref::<StringArray>().unwrap();
+            Value::String(values.value(dict.key(row).unwrap()).to_string())
+        }
+        other => return Err(anyhow!("reverse: unsupported column data type {:?}", other)),
+    };
+    Ok(value)
+}
+
+/// Reverse path: read a Parquet file and write it back out as `.jsonl.zstd`,
+/// one JSON object per row.
+pub fn parquet_to_jsonl(input_path: &PathBuf, output_path: &PathBuf) -> Result<usize, Error> {
+    let rows = reconstruct_rows(input_path)?;
+
+    let mut jsonl = String::new();
+    for row in &rows {
+        jsonl.push_str(&serde_json::to_string(row)?);
+        jsonl.push('\n');
+    }
+
+    let compressed = zstd::stream::encode_all(Cursor::new(jsonl.as_bytes()), 0)
+        .map_err(|e| anyhow!("reverse: failed to zstd-compress output: {}", e))?;
+    write_mem_to_pathbuf(&compressed, output_path)?;
+
+    Ok(rows.len())
+}
+
+/*=================================================================
+=                       ROUND-TRIP VERIFY                         =
+=================================================================*/
+
+pub struct VerifyReport {
+    pub rows_checked: usize,
+    pub mismatches: Vec<String>,
+}
+
+/// Round-trip a freshly written Parquet file back to JSON and compare each
+/// extracted column against what the schema would have pulled straight out
+/// of the original JSONL, to audit lossy extraction (e.g. the
+/// `metadata.WARC-Record-ID` nesting collapsing to a flat `id` column).
+///
+/// `kept_line_indices` (from `convert::ConversionStats`) maps each
+/// reconstructed row back to its 0-based source line: rows dropped by
+/// `--filter` or quarantined by `jsonl_to_parquet` mean row N and line N
+/// diverge as soon as anything upstream of row N was skipped, so a blind
+/// positional zip of source lines against reconstructed rows would compare
+/// the wrong pairs.
+///
+/// `plugin`, when set, must be the same plugin `jsonl_to_parquet` wrote the
+/// Parquet file with: a plugin replaces the schema's pointer/extractor
+/// fields entirely, so "expected" has to come from re-running the plugin on
+/// each kept line rather than `resolve_pointer`/`find_max_item` against
+/// JSON pointers the plugin never consulted.
+pub fn verify_round_trip(
+    original_input_path: &PathBuf,
+    parquet_path: &PathBuf,
+    schema_config: &crate::schema::SchemaConfig,
+    kept_line_indices: &[usize],
+    plugin: Option<&crate::plugin::Plugin>,
+) -> Result<VerifyReport, Error> {
+    use crate::schema::{find_max_item, resolve_pointer, Extractor};
+
+    let original_lines: Vec<String> = read_pathbuf_to_mem(original_input_path)?
+        .lines()
+        .collect::<Result<_, _>>()?;
+    let reconstructed_rows = reconstruct_rows(parquet_path)?;
+
+    if kept_line_indices.len() != reconstructed_rows.len() {
+        return Err(anyhow!(
+            "verify: {} kept line indices but {} reconstructed rows in {:?}",
+            kept_line_indices.len(), reconstructed_rows.len(), parquet_path
+        ));
+    }
+
+    let mut mismatches = Vec::new();
+    let mut rows_checked = 0usize;
+
+    for (&line_no, reconstructed) in kept_line_indices.iter().zip(reconstructed_rows.iter()) {
+        let line = original_lines.get(line_no).ok_or_else(|| {
+            anyhow!("verify: kept line index {} out of range for {:?}", line_no, original_input_path)
+        })?;
+        rows_checked += 1;
+
+        if let Some(plugin) = plugin {
+            let columns = plugin.extract(line)?.ok_or_else(|| {
+                anyhow!("verify: plugin skipped line {} that was kept in {:?}", line_no, parquet_path)
+            })?;
+            for spec in &schema_config.columns {
+                let expected = columns.get(&spec.name).cloned().unwrap_or(Value::Null);
+                let actual = reconstructed.get(&spec.name).cloned().unwrap_or(Value::Null);
+                if !values_equal(&expected, &actual) {
+                    mismatches.push(format!(
+                        "line {}: column {:?} expected {} got {}",
+                        line_no, spec.name, expected, actual
+                    ));
+                }
+            }
+            continue;
+        }
+
+        let json: Value = serde_json::from_str(line)?;
+        for spec in &schema_config.columns {
+            let expected: Value = match &spec.extractor {
+                Extractor::Direct => resolve_pointer(&json, &spec.pointer).cloned().unwrap_or(Value::Null),
+                Extractor::MaxValueKey => find_max_item(&json, &spec.pointer)
+                    .map(|(k, _)| Value::String(k.to_string()))
+                    .unwrap_or(Value::Null),
+                Extractor::MaxValueScore => find_max_item(&json, &spec.pointer)
+                    .map(|(_, v)| Value::from(v))
+                    .unwrap_or(Value::Null),
+            };
+            let actual = reconstructed.get(&spec.name).cloned().unwrap_or(Value::Null);
+            if !values_equal(&expected, &actual) {
+                mismatches.push(format!(
+                    "line {}: column {:?} expected {} got {}",
+                    line_no, spec.name, expected, actual
+                ));
+            }
+        }
+    }
+
+    Ok(VerifyReport { rows_checked, mismatches })
+}
+
+/// Float columns round-trip through `f32`, so compare with a small epsilon
+/// instead of exact equality.
+fn values_equal(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Number(a), Value::Number(b)) => {
+            (a.as_f64().unwrap_or(f64::NAN) - b.as_f64().unwrap_or(f64::NAN)).abs() < 1e-4
+        }
+        _ => expected == actual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{self, FaultToleranceConfig, WriterConfig};
+    use crate::filter;
+    use crate::schema::{ColumnSpec, Extractor, SchemaConfig};
+    use arrow::datatypes::DataType;
+    use std::io::Write as _;
+
+    fn schema() -> SchemaConfig {
+        SchemaConfig {
+            columns: vec![ColumnSpec {
+                name: "text".into(),
+                data_type: DataType::Utf8,
+                pointer: "text".into(),
+                nullable: false,
+                extractor: Extractor::Direct,
+                dictionary: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn verify_round_trip_aligns_against_kept_lines_not_position() {
+        let input = std::env::temp_dir().join("reverse_test_verify.jsonl");
+        let mut file = std::fs::File::create(&input).unwrap();
+        writeln!(file, "{{\"text\": \"keep-0\"}}").unwrap();
+        writeln!(file, "{{\"text\": \"drop-1\"}}").unwrap();
+        writeln!(file, "{{\"text\": \"keep-2\"}}").unwrap();
+        drop(file);
+
+        let output = std::env::temp_dir().join("reverse_test_verify.parquet");
+        let predicate = filter::compile("text != \"drop-1\"").unwrap();
+        let stats = convert::jsonl_to_parquet(
+            &input, &output, &schema(), Some(&predicate), None,
+            &WriterConfig::default(), &FaultToleranceConfig::default(),
+        ).unwrap();
+        assert_eq!(stats.kept_line_indices, vec![0, 2]);
+
+        let report = verify_round_trip(&input, &output, &schema(), &stats.kept_line_indices, None).unwrap();
+        assert_eq!(report.rows_checked, 2);
+        assert!(report.mismatches.is_empty(), "unexpected mismatches: {:?}", report.mismatches);
+
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error};
+use arrow::array::{
+    ArrayRef, BooleanBuilder, Float32Builder, Float64Builder, Int64Builder, StringBuilder,
+    StringDictionaryBuilder,
+};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema};
+use serde::Deserialize;
+use serde_json::Value;
+
+/*=================================================================
+=                          COLUMN SPECS                           =
+=================================================================*/
+
+/// How a column's value is pulled out of a raw JSON record.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Extractor {
+    /// Follow `pointer` straight to a scalar value.
+    Direct,
+    /// Treat the object at `pointer` as a `key -> f64` map, find the entry
+    /// with the largest value, and take its key (generalizes the old
+    /// FineWeb "max fasttext score" logic). Pair with `MaxValueScore` on a
+    /// sibling column pointing at the same `pointer` to get both halves.
+    MaxValueKey,
+    /// Same search as `MaxValueKey`, but takes the winning value instead.
+    MaxValueScore,
+}
+
+fn default_extractor() -> Extractor {
+    Extractor::Direct
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnSpec {
+    pub name: String,
+    #[serde(with = "data_type_serde")]
+    pub data_type: DataType,
+    /// Dot/bracket JSON pointer into the record, e.g. `metadata.WARC-Record-ID`
+    /// or `scores[0]`.
+    pub pointer: String,
+    #[serde(default)]
+    pub nullable: bool,
+    #[serde(default = "default_extractor")]
+    pub extractor: Extractor,
+    /// Dictionary-encode this column in the Parquet writer (low-cardinality
+    /// strings such as `language` benefit most).
+    #[serde(default)]
+    pub dictionary: bool,
+}
+
+impl ColumnSpec {
+    /// The Arrow field type this column actually gets written as: plain
+    /// `Utf8` unless `dictionary` asks for `Dictionary<Int32, Utf8>`.
+    pub fn field_data_type(&self) -> DataType {
+        if self.dictionary && self.data_type == DataType::Utf8 {
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+        } else {
+            self.data_type.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SchemaConfig {
+    pub columns: Vec<ColumnSpec>,
+}
+
+impl SchemaConfig {
+    pub fn from_path(path: &PathBuf) -> Result<SchemaConfig, Error> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read schema config {:?}: {}", path, e))?;
+        let config: SchemaConfig = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Failed to parse schema config {:?}: {}", path, e))?;
+        Ok(config)
+    }
+
+    pub fn arrow_schema(&self) -> Schema {
+        Schema::new(
+            self.columns
+                .iter()
+                .map(|c| Field::new(&c.name, c.field_data_type(), c.nullable))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+mod data_type_serde {
+    use arrow::datatypes::DataType;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DataType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match s.as_str() {
+            "utf8" | "string" => Ok(DataType::Utf8),
+            "bool" | "boolean" => Ok(DataType::Boolean),
+            "int64" | "int" => Ok(DataType::Int64),
+            "float32" => Ok(DataType::Float32),
+            "float64" | "float" => Ok(DataType::Float64),
+            other => Err(serde::de::Error::custom(format!(
+                "unsupported schema data_type {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/*=================================================================
+=                          JSON POINTERS                          =
+=================================================================*/
+
+/// Resolve a dot/bracket pointer like `metadata.WARC-Record-ID` or
+/// `scores[0].value` against a JSON record.
+pub fn resolve_pointer<'a>(json: &'a Value, pointer: &str) -> Option<&'a Value> {
+    let mut current = json;
+    for segment in split_pointer(pointer) {
+        current = match segment {
+            PointerSegment::Key(key) => current.get(key)?,
+            PointerSegment::Index(idx) => current.get(idx)?,
+        };
+    }
+    Some(current)
+}
+
+enum PointerSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+fn split_pointer(pointer: &str) -> Vec<PointerSegment<'_>> {
+    let mut segments = Vec::new();
+    for dot_part in pointer.split('.') {
+        let mut rest = dot_part;
+        while let Some(open) = rest.find('[') {
+            if open > 0 {
+                segments.push(PointerSegment::Key(&rest[..open]));
+            }
+            let close = rest[open..].find(']').map(|i| i + open).unwrap_or(rest.len());
+            if let Ok(idx) = rest[open + 1..close].parse::<usize>() {
+                segments.push(PointerSegment::Index(idx));
+            }
+            rest = &rest[(close + 1).min(rest.len())..];
+        }
+        if !rest.is_empty() {
+            segments.push(PointerSegment::Key(rest));
+        }
+    }
+    segments
+}
+
+/// Generalizes the old `_find_max_item`: given the object at `pointer`,
+/// return the `(key, value)` pair with the largest `f64` value.
+pub fn find_max_item<'a>(json: &'a Value, pointer: &str) -> Option<(&'a str, f64)> {
+    resolve_pointer(json, pointer)?
+        .as_object()?
+        .iter()
+        .filter_map(|(key, value)| value.as_f64().map(|v| (key.as_str(), v)))
+        .max_by(|&(_, a), &(_, b)| a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/*=================================================================
+=                        DYNAMIC BUILDERS                         =
+=================================================================*/
+
+/// What a `Utf8`/`DictionaryUtf8` builder actually appends for a JSON
+/// value: strings pass through as-is, and every other non-null value
+/// (bools, numbers in a column promoted to `Utf8`, and the arrays/objects
+/// `infer_value_type` folds into `Utf8`) is serialized back to its JSON
+/// text rather than silently dropped.
+fn utf8_repr(value: Option<&Value>) -> Option<String> {
+    let value = value?;
+    if let Some(s) = value.as_str() {
+        return Some(s.to_string());
+    }
+    if value.is_null() {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+/// A column builder that can be driven generically from a `ColumnSpec`,
+/// instead of hand-writing one `*Builder` field per FineWeb column.
+pub enum ColumnBuilder {
+    Utf8(StringBuilder),
+    DictionaryUtf8(StringDictionaryBuilder<Int32Type>),
+    Boolean(BooleanBuilder),
+    Int64(Int64Builder),
+    Float32(Float32Builder),
+    Float64(Float64Builder),
+}
+
+impl ColumnBuilder {
+    pub fn new(spec: &ColumnSpec) -> Result<ColumnBuilder, Error> {
+        match spec.data_type {
+            DataType::Utf8 if spec.dictionary => {
+                Ok(ColumnBuilder::DictionaryUtf8(StringDictionaryBuilder::new()))
+            }
+            DataType::Utf8 => Ok(ColumnBuilder::Utf8(StringBuilder::new())),
+            DataType::Boolean => Ok(ColumnBuilder::Boolean(BooleanBuilder::new())),
+            DataType::Int64 => Ok(ColumnBuilder::Int64(Int64Builder::new())),
+            DataType::Float32 => Ok(ColumnBuilder::Float32(Float32Builder::new())),
+            DataType::Float64 => Ok(ColumnBuilder::Float64(Float64Builder::new())),
+            ref other => Err(anyhow!("ColumnBuilder: unsupported data type {:?}", other)),
+        }
+    }
+
+    pub fn append_value(&mut self, value: Option<&Value>) {
+        match self {
+            ColumnBuilder::Utf8(b) => b.append_option(utf8_repr(value).as_deref()),
+            ColumnBuilder::DictionaryUtf8(b) => match utf8_repr(value) {
+                Some(v) => { b.append(v.as_str()).ok(); }
+                None => b.append_null(),
+            },
+            ColumnBuilder::Boolean(b) => b.append_option(value.and_then(|v| v.as_bool())),
+            ColumnBuilder::Int64(b) => b.append_option(value.and_then(|v| v.as_i64())),
+            ColumnBuilder::Float32(b) => b.append_option(value.and_then(|v| v.as_f64()).map(|v| v as f32)),
+            ColumnBuilder::Float64(b) => b.append_option(value.and_then(|v| v.as_f64())),
+        }
+    }
+
+    pub fn append_str(&mut self, value: Option<&str>) {
+        match self {
+            ColumnBuilder::Utf8(b) => b.append_option(value),
+            ColumnBuilder::DictionaryUtf8(b) => match value {
+                Some(v) => { b.append(v).ok(); }
+                None => b.append_null(),
+            },
+            _ => self.append_value(None),
+        }
+    }
+
+    pub fn append_f64(&mut self, value: Option<f64>) {
+        match self {
+            ColumnBuilder::Float32(b) => b.append_option(value.map(|v| v as f32)),
+            ColumnBuilder::Float64(b) => b.append_option(value),
+            ColumnBuilder::Int64(b) => b.append_option(value.map(|v| v as i64)),
+            _ => self.append_value(None),
+        }
+    }
+
+    pub fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Utf8(b) => Arc::new(b.finish()) as ArrayRef,
+            ColumnBuilder::DictionaryUtf8(b) => Arc::new(b.finish()) as ArrayRef,
+            ColumnBuilder::Boolean(b) => Arc::new(b.finish()) as ArrayRef,
+            ColumnBuilder::Int64(b) => Arc::new(b.finish()) as ArrayRef,
+            ColumnBuilder::Float32(b) => Arc::new(b.finish()) as ArrayRef,
+            ColumnBuilder::Float64(b) => Arc::new(b.finish()) as ArrayRef,
+        }
+    }
+}
+
+
+/*=================================================================
+=                         TYPE INFERENCE                          =
+=================================================================*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum InferredType {
+    Int64,
+    Float64,
+    Utf8,
+}
+
+impl InferredType {
+    fn promote(self, other: InferredType) -> InferredType {
+        std::cmp::max(self, other)
+    }
+
+    fn to_data_type(self) -> DataType {
+        match self {
+            InferredType::Int64 => DataType::Int64,
+            InferredType::Float64 => DataType::Float64,
+            InferredType::Utf8 => DataType::Utf8,
+        }
+    }
+}
+
+fn infer_value_type(value: &Value) -> Option<InferredType> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some(InferredType::Utf8), // bools are rare/ambiguous in this corpus; fold into string
+        Value::Number(n) => {
+            if n.is_i64() || n.is_u64() {
+                Some(InferredType::Int64)
+            } else {
+                Some(InferredType::Float64)
+            }
+        }
+        Value::String(_) => Some(InferredType::Utf8),
+        // Nested values aren't flattened into columns; fold them into
+        // `Utf8` and serialize them back to JSON text on append (see
+        // `utf8_repr`) rather than dropping them.
+        Value::Array(_) | Value::Object(_) => Some(InferredType::Utf8),
+    }
+}
+
+/// Sample the first `sample_size` lines of `contents`, union the observed
+/// top-level keys and their types (promoting int -> float -> string on
+/// conflict), and mark any key that's absent from some sampled rows as
+/// nullable. Used when no `--schema` config is supplied.
+pub fn infer_schema(contents: &str, sample_size: usize) -> Result<SchemaConfig, Error> {
+    let mut types: BTreeMap<String, InferredType> = BTreeMap::new();
+    let mut seen_count: BTreeMap<String, usize> = BTreeMap::new();
+    let mut row_count = 0usize;
+
+    for line in contents.lines().take(sample_size) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let json: Value = serde_json::from_str(line)
+            .map_err(|e| anyhow!("schema inference: failed to parse line: {}", e))?;
+        let obj = json
+            .as_object()
+            .ok_or_else(|| anyhow!("schema inference: top-level JSON value is not an object"))?;
+        row_count += 1;
+        for (key, value) in obj.iter() {
+            if let Some(inferred) = infer_value_type(value) {
+                types
+                    .entry(key.clone())
+                    .and_modify(|t| *t = t.promote(inferred))
+                    .or_insert(inferred);
+                *seen_count.entry(key.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let columns = types
+        .into_iter()
+        .map(|(key, inferred)| {
+            let nullable = seen_count.get(&key).copied().unwrap_or(0) < row_count;
+            ColumnSpec {
+                name: key.clone(),
+                data_type: inferred.to_data_type(),
+                pointer: key,
+                nullable,
+                extractor: Extractor::Direct,
+                dictionary: false,
+            }
+        })
+        .collect();
+
+    Ok(SchemaConfig { columns })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn resolve_pointer_dot_and_bracket_segments() {
+        let json = json!({
+            "metadata": {"WARC-Record-ID": "abc123"},
+            "scores": [{"value": 0.5}, {"value": 0.9}],
+        });
+        assert_eq!(
+            resolve_pointer(&json, "metadata.WARC-Record-ID"),
+            Some(&Value::String("abc123".to_string()))
+        );
+        assert_eq!(
+            resolve_pointer(&json, "scores[1].value"),
+            Some(&Value::from(0.9))
+        );
+        assert_eq!(resolve_pointer(&json, "missing.key"), None);
+        assert_eq!(resolve_pointer(&json, "scores[5].value"), None);
+    }
+
+    #[test]
+    fn find_max_item_picks_largest_value() {
+        let json = json!({"lang_scores": {"en": 0.2, "fr": 0.9, "de": 0.5}});
+        let (key, value) = find_max_item(&json, "lang_scores").unwrap();
+        assert_eq!(key, "fr");
+        assert_eq!(value, 0.9);
+    }
+
+    #[test]
+    fn infer_schema_promotes_int_to_float_to_string() {
+        let contents = "\
+            {\"a\": 1, \"b\": 1}\n\
+            {\"a\": 1.5, \"b\": 1}\n\
+            {\"a\": \"oops\", \"b\": 1}\n";
+        let config = infer_schema(contents, 10).unwrap();
+        let a = config.columns.iter().find(|c| c.name == "a").unwrap();
+        let b = config.columns.iter().find(|c| c.name == "b").unwrap();
+        assert_eq!(a.data_type, DataType::Utf8);
+        assert_eq!(b.data_type, DataType::Int64);
+    }
+
+    #[test]
+    fn infer_schema_marks_absent_keys_nullable() {
+        let contents = "{\"a\": 1, \"b\": 2}\n{\"a\": 3}\n";
+        let config = infer_schema(contents, 10).unwrap();
+        let a = config.columns.iter().find(|c| c.name == "a").unwrap();
+        let b = config.columns.iter().find(|c| c.name == "b").unwrap();
+        assert!(!a.nullable);
+        assert!(b.nullable);
+    }
+
+    fn spec(data_type: DataType, dictionary: bool) -> ColumnSpec {
+        ColumnSpec {
+            name: "col".to_string(),
+            data_type,
+            pointer: "col".to_string(),
+            nullable: true,
+            extractor: Extractor::Direct,
+            dictionary,
+        }
+    }
+
+    #[test]
+    fn column_builder_round_trips_each_data_type() {
+        let utf8_spec = spec(DataType::Utf8, false);
+        let mut b = ColumnBuilder::new(&utf8_spec).unwrap();
+        b.append_value(Some(&json!("hello")));
+        b.append_value(None);
+        assert_eq!(b.finish().len(), 2);
+
+        let dict_spec = spec(DataType::Utf8, true);
+        let mut b = ColumnBuilder::new(&dict_spec).unwrap();
+        b.append_value(Some(&json!("en")));
+        assert_eq!(b.finish().data_type(), &dict_spec.field_data_type());
+
+        let bool_spec = spec(DataType::Boolean, false);
+        let mut b = ColumnBuilder::new(&bool_spec).unwrap();
+        b.append_value(Some(&json!(true)));
+        assert_eq!(b.finish().len(), 1);
+
+        let int_spec = spec(DataType::Int64, false);
+        let mut b = ColumnBuilder::new(&int_spec).unwrap();
+        b.append_value(Some(&json!(42)));
+        assert_eq!(b.finish().len(), 1);
+
+        let f32_spec = spec(DataType::Float32, false);
+        let mut b = ColumnBuilder::new(&f32_spec).unwrap();
+        b.append_value(Some(&json!(0.5)));
+        assert_eq!(b.finish().data_type(), &DataType::Float32);
+
+        let f64_spec = spec(DataType::Float64, false);
+        let mut b = ColumnBuilder::new(&f64_spec).unwrap();
+        b.append_value(Some(&json!(0.5)));
+        assert_eq!(b.finish().data_type(), &DataType::Float64);
+    }
+
+    #[test]
+    fn column_builder_serializes_nested_values_instead_of_nulling() {
+        let utf8_spec = spec(DataType::Utf8, false);
+        let mut b = ColumnBuilder::new(&utf8_spec).unwrap();
+        b.append_value(Some(&json!({"k": "v"})));
+        let array = b.finish();
+        let strings = array.as_any().downcast_ref::<arrow::array::StringArray>().unwrap();
+        assert!(!strings.is_null(0));
+        assert_eq!(strings.value(0), "{\"k\":\"v\"}");
+    }
+}